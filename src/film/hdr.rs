@@ -0,0 +1,59 @@
+use super::film::{Film, Format, Save};
+use super::tonemap::Tonemap;
+use math::Vector3;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub struct Hdr;
+
+impl Format for Hdr {
+  fn ext() -> &'static str {
+    "hdr"
+  }
+}
+
+impl Save<Vector3> for Hdr {
+  type Output = Vector3;
+
+  fn save<M>(film: &Film<Vector3>, path: &Path, tonemap: M)
+  where
+    M: Tonemap<Input = Vector3, Output = Vector3>,
+  {
+    let file = File::create(path).expect("failed to create .hdr file");
+    let mut w = BufWriter::new(file);
+
+    write!(w, "#?RADIANCE\n").unwrap();
+    write!(w, "FORMAT=32-bit_rle_rgbe\n\n").unwrap();
+    write!(w, "-Y {} +X {}\n", film.height, film.width).unwrap();
+
+    for pixel in &film.data {
+      w.write_all(&rgbe(tonemap.map(*pixel))).unwrap();
+    }
+  }
+}
+
+// Radiance RGBE: shared exponent so linear radiance survives without clamping
+fn rgbe(c: Vector3) -> [u8; 4] {
+  let m = c.x.max(c.y).max(c.z);
+  if m < 1e-32 {
+    return [0, 0, 0, 0];
+  }
+  let (mantissa, exponent) = frexp(m);
+  let scale = mantissa * 256.0 / m;
+  [
+    (c.x * scale) as u8,
+    (c.y * scale) as u8,
+    (c.z * scale) as u8,
+    (exponent + 128) as u8,
+  ]
+}
+
+// f32 has no std frexp: split IEEE754 bits into mantissa in [0.5, 1) and exponent
+fn frexp(x: f32) -> (f32, i32) {
+  let bits = x.to_bits();
+  let biased_exponent = ((bits >> 23) & 0xff) as i32;
+  let exponent = biased_exponent - 126;
+  let mantissa_bits = (bits & !(0xffu32 << 23)) | (126 << 23);
+  (f32::from_bits(mantissa_bits), exponent)
+}
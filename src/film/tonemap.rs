@@ -0,0 +1,23 @@
+use math::Vector3;
+
+pub trait Tonemap {
+  type Input;
+  type Output;
+
+  fn map(&self, value: Self::Input) -> Self::Output;
+}
+
+/**
+ * そのまま通すトーンマッピング。線形なレンジを保ったまま保存したい
+ * HDR出力のために使う
+ */
+pub struct Identity;
+
+impl Tonemap for Identity {
+  type Input = Vector3;
+  type Output = Vector3;
+
+  fn map(&self, value: Vector3) -> Vector3 {
+    value
+  }
+}
@@ -0,0 +1,120 @@
+use super::matrix4::Matrix4;
+use super::vector3::Vector3;
+use std::ops::Mul;
+
+/**
+ * 単位クォータニオン (w, x, y, z) による回転表現。
+ * axis_angle行列と異なり、球面線形補間(slerp)でなめらかに補間できる
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Quaternion {
+  pub w: f32,
+  pub x: f32,
+  pub y: f32,
+  pub z: f32,
+}
+
+impl Quaternion {
+  pub fn new(w: f32, x: f32, y: f32, z: f32) -> Quaternion {
+    Quaternion { w, x, y, z }
+  }
+
+  pub fn identity() -> Quaternion {
+    Quaternion::new(1.0, 0.0, 0.0, 0.0)
+  }
+
+  pub fn from_axis_angle(axis: Vector3, t: f32) -> Quaternion {
+    let half = t * 0.5;
+    let s = half.sin();
+    Quaternion::new(half.cos(), axis.x * s, axis.y * s, axis.z * s)
+  }
+
+  pub fn length(&self) -> f32 {
+    self.dot(self).sqrt()
+  }
+
+  pub fn dot(&self, rhs: &Quaternion) -> f32 {
+    self.w * rhs.w + self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+  }
+
+  pub fn normalize(&self) -> Quaternion {
+    let len = self.length();
+    Quaternion::new(self.w / len, self.x / len, self.y / len, self.z / len)
+  }
+
+  pub fn conjugate(&self) -> Quaternion {
+    Quaternion::new(self.w, -self.x, -self.y, -self.z)
+  }
+
+  /**
+   * 球面線形補間。内積が負なら短い経路を取るようbを反転し、
+   * ほぼ平行な場合はゼロ割りを避けるため正規化線形補間にフォールバックする
+   */
+  pub fn slerp(a: &Quaternion, b: &Quaternion, t: f32) -> Quaternion {
+    let mut d = a.dot(b);
+    let mut b = *b;
+    if d < 0.0 {
+      b = Quaternion::new(-b.w, -b.x, -b.y, -b.z);
+      d = -d;
+    }
+
+    if d > 0.9995 {
+      return Quaternion::new(
+        a.w + (b.w - a.w) * t,
+        a.x + (b.x - a.x) * t,
+        a.y + (b.y - a.y) * t,
+        a.z + (b.z - a.z) * t,
+      )
+      .normalize();
+    }
+
+    let theta = d.acos();
+    let sin_theta = theta.sin();
+    let sa = ((1.0 - t) * theta).sin() / sin_theta;
+    let sb = (t * theta).sin() / sin_theta;
+    Quaternion::new(
+      a.w * sa + b.w * sb,
+      a.x * sa + b.x * sb,
+      a.y * sa + b.y * sb,
+      a.z * sa + b.z * sb,
+    )
+  }
+}
+
+impl<'a> Mul for &'a Quaternion {
+  type Output = Quaternion;
+
+  // Hamilton product
+  fn mul(self, rhs: &Quaternion) -> Quaternion {
+    Quaternion::new(
+      self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+      self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+      self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+      self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+    )
+  }
+}
+
+impl From<Quaternion> for Matrix4 {
+  fn from(q: Quaternion) -> Matrix4 {
+    let (w, x, y, z) = (q.w, q.x, q.y, q.z);
+    Matrix4::new([
+      1.0 - 2.0 * (y * y + z * z),
+      2.0 * (x * y - w * z),
+      2.0 * (x * z + w * y),
+      0.0,
+      2.0 * (x * y + w * z),
+      1.0 - 2.0 * (x * x + z * z),
+      2.0 * (y * z - w * x),
+      0.0,
+      2.0 * (x * z - w * y),
+      2.0 * (y * z + w * x),
+      1.0 - 2.0 * (x * x + y * y),
+      0.0,
+      0.0,
+      0.0,
+      0.0,
+      1.0,
+    ])
+  }
+}